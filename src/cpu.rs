@@ -0,0 +1,208 @@
+use crate::flags::{FlagChange, Flags};
+use crate::instruction::{Instruction, Mnemonic, Width};
+use crate::operand::Operand;
+use crate::reg::Reg;
+
+/// The eight general-purpose word registers, in 8086 ModRM order: ax, cx, dx, bx,
+/// sp, bp, si, di. `al`/`ah` etc. are overlays onto the low/high byte of their word,
+/// not separate storage.
+const WORD_REGISTER_COUNT: usize = 8;
+
+/// Register + memory state for the `--exec` simulation mode.
+pub struct Cpu {
+    registers: [u16; WORD_REGISTER_COUNT],
+    pub ip: u16,
+    flags: Flags,
+    memory: Box<[u8; 0x10000]>,
+}
+
+impl Cpu {
+    pub fn new() -> Self {
+        Cpu {
+            registers: [0; WORD_REGISTER_COUNT],
+            ip: 0,
+            flags: Flags::default(),
+            memory: Box::new([0; 0x10000]),
+        }
+    }
+
+    pub fn get_register(&self, reg: &Reg) -> u16 {
+        let code = *reg as u8;
+
+        if code & 0b1000 != 0 {
+            self.registers[(code & 0b111) as usize]
+        } else {
+            let word = self.registers[(code & 0b011) as usize];
+            if code & 0b100 != 0 {
+                (word >> 8) & 0x00ff
+            } else {
+                word & 0x00ff
+            }
+        }
+    }
+
+    pub fn set_register(&mut self, reg: &Reg, value: u16) {
+        let code = *reg as u8;
+
+        if code & 0b1000 != 0 {
+            self.registers[(code & 0b111) as usize] = value;
+        } else {
+            let word = &mut self.registers[(code & 0b011) as usize];
+            *word = if code & 0b100 != 0 {
+                (*word & 0x00ff) | ((value & 0x00ff) << 8)
+            } else {
+                (*word & 0xff00) | (value & 0x00ff)
+            };
+        }
+    }
+
+    fn effective_address(&self, base: Option<Reg>, index: Option<Reg>, disp: i16) -> u16 {
+        let mut address = disp as u16;
+        if let Some(base) = base {
+            address = address.wrapping_add(self.get_register(&base));
+        }
+        if let Some(index) = index {
+            address = address.wrapping_add(self.get_register(&index));
+        }
+        address
+    }
+
+    /// Read a single memory byte, for tools (e.g. the debugger's hexdump) that don't
+    /// care about operand width.
+    pub fn read_byte(&self, address: u16) -> u8 {
+        self.memory[address as usize]
+    }
+
+    fn read_mem8(&self, address: u16) -> u16 {
+        self.memory[address as usize] as u16
+    }
+
+    fn write_mem8(&mut self, address: u16, value: u16) {
+        self.memory[address as usize] = value as u8;
+    }
+
+    fn read_mem16(&self, address: u16) -> u16 {
+        u16::from_le_bytes([
+            self.memory[address as usize],
+            self.memory[address.wrapping_add(1) as usize],
+        ])
+    }
+
+    fn write_mem16(&mut self, address: u16, value: u16) {
+        let bytes = value.to_le_bytes();
+        self.memory[address as usize] = bytes[0];
+        self.memory[address.wrapping_add(1) as usize] = bytes[1];
+    }
+
+    fn read_operand(&self, operand: &Operand, width: Width) -> u16 {
+        match operand {
+            Operand::Register(reg) => self.get_register(reg),
+            Operand::Immediate(value) => *value as u16,
+            Operand::Memory { base, index, disp } => {
+                let address = self.effective_address(*base, *index, *disp);
+                match width {
+                    Width::Byte => self.read_mem8(address),
+                    Width::Word => self.read_mem16(address),
+                }
+            }
+            Operand::Relative(_) => unreachable!("jump targets aren't readable operands"),
+        }
+    }
+
+    fn write_operand(&mut self, operand: &Operand, width: Width, value: u16) {
+        match operand {
+            Operand::Register(reg) => self.set_register(reg, value),
+            Operand::Memory { base, index, disp } => {
+                let address = self.effective_address(*base, *index, *disp);
+                match width {
+                    Width::Byte => self.write_mem8(address, value),
+                    Width::Word => self.write_mem16(address, value),
+                }
+            }
+            Operand::Immediate(_) => unreachable!("cannot write to an immediate operand"),
+            Operand::Relative(_) => unreachable!("jump targets aren't writable operands"),
+        }
+    }
+
+    fn decrement_cx(&mut self) -> u16 {
+        let cx = self.get_register(&Reg::cx).wrapping_sub(1);
+        self.set_register(&Reg::cx, cx);
+        cx
+    }
+
+    /// Does this conditional jump/loop take the branch, given the current flags?
+    /// LOOP/LOOPZ/LOOPNZ decrement `cx` first, as a side effect of the test itself.
+    fn branch_taken(&mut self, mnemonic: Mnemonic) -> bool {
+        match mnemonic {
+            Mnemonic::Je => self.flags.zero,
+            Mnemonic::Jne => !self.flags.zero,
+            Mnemonic::Jl => self.flags.sign != self.flags.overflow,
+            Mnemonic::Jge => self.flags.sign == self.flags.overflow,
+            Mnemonic::Jle => self.flags.zero || self.flags.sign != self.flags.overflow,
+            Mnemonic::Jg => !self.flags.zero && self.flags.sign == self.flags.overflow,
+            Mnemonic::Jb => self.flags.carry,
+            Mnemonic::Jae => !self.flags.carry,
+            Mnemonic::Jbe => self.flags.carry || self.flags.zero,
+            Mnemonic::Ja => !self.flags.carry && !self.flags.zero,
+            Mnemonic::Jp => self.flags.parity,
+            Mnemonic::Jnp => !self.flags.parity,
+            Mnemonic::Jo => self.flags.overflow,
+            Mnemonic::Jno => !self.flags.overflow,
+            Mnemonic::Js => self.flags.sign,
+            Mnemonic::Jns => !self.flags.sign,
+            Mnemonic::Jcxz => self.get_register(&Reg::cx) == 0,
+            Mnemonic::Loop => self.decrement_cx() != 0,
+            Mnemonic::Loopz => self.decrement_cx() != 0 && self.flags.zero,
+            Mnemonic::Loopnz => self.decrement_cx() != 0 && !self.flags.zero,
+            _ => unreachable!("not a branch mnemonic"),
+        }
+    }
+
+    /// Apply a decoded instruction's effect to this CPU's registers, memory and flags,
+    /// and advance `ip` to `next_ip` (the address of the following instruction) or to a
+    /// taken branch's target. Returns the flags that changed, for trace output.
+    pub fn execute(&mut self, instruction: &Instruction, next_ip: u16) -> Vec<FlagChange> {
+        let previous_flags = self.flags;
+
+        self.ip = next_ip;
+
+        match instruction.mnemonic {
+            Mnemonic::Mov => {
+                let value = self.read_operand(&instruction.src, instruction.width);
+                self.write_operand(&instruction.dest, instruction.width, value);
+            }
+            Mnemonic::Add | Mnemonic::Sub | Mnemonic::Cmp => {
+                let dest = self.read_operand(&instruction.dest, instruction.width);
+                let src = self.read_operand(&instruction.src, instruction.width);
+                let is_sub = matches!(instruction.mnemonic, Mnemonic::Sub | Mnemonic::Cmp);
+                let result = self.flags.apply_arithmetic(dest, src, instruction.width, is_sub);
+
+                if !matches!(instruction.mnemonic, Mnemonic::Cmp) {
+                    self.write_operand(&instruction.dest, instruction.width, result);
+                }
+            }
+            _ if instruction.mnemonic.is_single_operand() => {
+                if self.branch_taken(instruction.mnemonic) {
+                    let Operand::Relative(disp) = instruction.dest else {
+                        unreachable!("single-operand branch without a relative target")
+                    };
+                    self.ip = self.ip.wrapping_add(disp as u16);
+                }
+            }
+            _ => unreachable!(),
+        }
+
+        self.flags.changes_from(previous_flags)
+    }
+
+    pub fn dump_registers(&self) {
+        const NAMES: [&str; WORD_REGISTER_COUNT] = ["ax", "cx", "dx", "bx", "sp", "bp", "si", "di"];
+
+        println!("\nFinal registers:");
+        for (name, value) in NAMES.iter().zip(self.registers.iter()) {
+            println!("      {}: {:#06x}", name, value);
+        }
+        println!("      ip: {:#06x}", self.ip);
+        println!("   flags: {}", self.flags);
+    }
+}
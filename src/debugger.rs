@@ -0,0 +1,177 @@
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::io::{self, Cursor, Seek, SeekFrom, Write};
+
+use crate::cpu::Cpu;
+use crate::decode::decode;
+
+/// An interactive, single-instruction debugger over a `Cpu` running a loaded binary.
+/// Modeled as a simple read-command/act loop: each line is either a new command or,
+/// if blank, a repeat of whatever command ran last (so pressing enter keeps stepping).
+pub struct Debugger {
+    cpu: Cpu,
+    cursor: Cursor<Vec<u8>>,
+    breakpoints: BTreeSet<u16>,
+    last_command: Option<String>,
+    trace: bool,
+}
+
+impl Debugger {
+    pub fn new(binary: Vec<u8>) -> Self {
+        Debugger {
+            cpu: Cpu::new(),
+            cursor: Cursor::new(binary),
+            breakpoints: BTreeSet::new(),
+            last_command: None,
+            trace: false,
+        }
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        println!("8086 debugger. Type `help` for commands.");
+
+        let stdin = io::stdin();
+        loop {
+            print!("(dbg) ");
+            io::stdout().flush()?;
+
+            let mut line = String::new();
+            if stdin.read_line(&mut line)? == 0 {
+                break; // EOF on stdin
+            }
+
+            let line = line.trim();
+            let command = if line.is_empty() {
+                match &self.last_command {
+                    Some(previous) => previous.clone(),
+                    None => continue,
+                }
+            } else {
+                line.to_string()
+            };
+
+            if !self.dispatch(&command)? {
+                break;
+            }
+
+            self.last_command = Some(command);
+        }
+
+        Ok(())
+    }
+
+    /// Returns `false` when the debugger should exit.
+    fn dispatch(&mut self, command: &str) -> Result<bool> {
+        let mut parts = command.split_whitespace();
+        let Some(name) = parts.next() else {
+            return Ok(true);
+        };
+
+        match name {
+            "help" | "h" => self.print_help(),
+            "step" | "s" => {
+                let count: u32 = parts.next().and_then(|n| n.parse().ok()).unwrap_or(1);
+                for _ in 0..count {
+                    if !self.step() {
+                        break;
+                    }
+                }
+            }
+            "continue" | "c" => self.continue_until_breakpoint(),
+            "break" | "b" => {
+                if let Some(address) = parts.next().and_then(parse_address) {
+                    self.breakpoints.insert(address);
+                    println!("breakpoint set at {:#06x}", address);
+                }
+            }
+            "clear" => {
+                if let Some(address) = parts.next().and_then(parse_address) {
+                    self.breakpoints.remove(&address);
+                    println!("breakpoint cleared at {:#06x}", address);
+                }
+            }
+            "regs" | "r" => self.cpu.dump_registers(),
+            "mem" | "m" => {
+                let address = parts.next().and_then(parse_address).unwrap_or(0);
+                let len = parts.next().and_then(|n| n.parse().ok()).unwrap_or(16);
+                self.hexdump(address, len);
+            }
+            "trace" => {
+                self.trace = !self.trace;
+                println!("trace: {}", if self.trace { "on" } else { "off" });
+            }
+            "quit" | "q" => return Ok(false),
+            _ => println!("unknown command: {} (try `help`)", name),
+        }
+
+        Ok(true)
+    }
+
+    fn print_help(&self) {
+        println!("  step|s [n]     execute one instruction (or n of them)");
+        println!("  continue|c     run until a breakpoint or end of input");
+        println!("  break|b <addr> set a breakpoint at an ip (hex, e.g. 1a or 0x1a)");
+        println!("  clear <addr>   clear a breakpoint");
+        println!("  regs|r         dump registers and flags");
+        println!("  mem|m <addr> [len]  hexdump memory starting at addr");
+        println!("  trace          toggle per-instruction tracing");
+        println!("  quit|q         exit the debugger");
+        println!("  <enter>        repeat the last command");
+    }
+
+    /// Decode and execute the instruction at the current `ip`. Returns `false` at end of input.
+    fn step(&mut self) -> bool {
+        let address = self.cpu.ip;
+        self.cursor.seek(SeekFrom::Start(address as u64)).expect("seek within loaded binary");
+
+        let instruction = match decode(&mut self.cursor) {
+            Ok(Some(instruction)) => instruction,
+            Ok(None) => return false,
+            Err(error) => {
+                println!("decode error: {}", error);
+                return false;
+            }
+        };
+
+        let next_ip = self.cursor.position() as u16;
+        let changes = self.cpu.execute(&instruction, next_ip);
+
+        if self.trace {
+            print!("{:#06x}: {}", address, instruction);
+            for change in changes {
+                print!(" ; {}", change);
+            }
+            println!();
+        }
+
+        true
+    }
+
+    fn continue_until_breakpoint(&mut self) {
+        while self.step() {
+            if self.breakpoints.contains(&self.cpu.ip) {
+                println!("hit breakpoint at {:#06x}", self.cpu.ip);
+                return;
+            }
+        }
+
+        println!("end of input at {:#06x}", self.cpu.ip);
+    }
+
+    fn hexdump(&self, address: u16, len: u16) {
+        for chunk_start in (0..len).step_by(16) {
+            let base = address.wrapping_add(chunk_start);
+            print!("{:#06x}: ", base);
+
+            let row_len = len.saturating_sub(chunk_start).min(16);
+            for offset in 0..row_len {
+                print!("{:02x} ", self.cpu.read_byte(base.wrapping_add(offset)));
+            }
+            println!();
+        }
+    }
+}
+
+fn parse_address(token: &str) -> Option<u16> {
+    u16::from_str_radix(token.trim_start_matches("0x"), 16).ok()
+}
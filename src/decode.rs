@@ -0,0 +1,381 @@
+use anyhow::{bail, Result};
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::{Cursor, Seek};
+
+use crate::instruction::{Instruction, Mnemonic, Width};
+use crate::operand::Operand;
+use crate::reg::Reg;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[allow(dead_code)]
+pub enum ModField {
+    MemoryNoDisplacement = 0b00,
+    MemoryDisplacement8bit = 0b01,
+    MemoryDisplacement16bit = 0b10,
+    RegisterNoDisplacement = 0b11,
+}
+
+impl TryFrom<u8> for ModField {
+    type Error = anyhow::Error;
+
+    fn try_from(code: u8) -> Result<Self> {
+        Ok(match code {
+            0b00 => ModField::MemoryNoDisplacement,
+            0b01 => ModField::MemoryDisplacement8bit,
+            0b10 => ModField::MemoryDisplacement16bit,
+            0b11 => ModField::RegisterNoDisplacement,
+            _ => bail!("mod field {:#04b} doesn't fit in the 2 bits a ModRM byte provides", code),
+        })
+    }
+}
+
+fn width_of(w_field: u8) -> Width {
+    if w_field == 0 {
+        Width::Byte
+    } else {
+        Width::Word
+    }
+}
+
+/// Resolve a `mod`/`rm` memory operand to its symbolic base/index/disp form, per table 4-10.
+fn decode_memory_operand(mode: ModField, rm: u8, disp: i16) -> Operand {
+    let (base, index) = match (mode, rm) {
+        (ModField::MemoryNoDisplacement, 0b110) => (None, None), // direct address
+        (_, 0b110) => (Some(Reg::bp), None),
+        (_, 0b000) => (Some(Reg::bx), Some(Reg::si)),
+        (_, 0b001) => (Some(Reg::bx), Some(Reg::di)),
+        (_, 0b010) => (Some(Reg::bp), Some(Reg::si)),
+        (_, 0b011) => (Some(Reg::bp), Some(Reg::di)),
+        (_, 0b100) => (None, Some(Reg::si)),
+        (_, 0b101) => (None, Some(Reg::di)),
+        (_, 0b111) => (Some(Reg::bx), None),
+        _ => unreachable!(),
+    };
+
+    Operand::Memory { base, index, disp }
+}
+
+/// Shared by MOV, ADD, SUB and CMP: all four use the same `mod`/`reg`/`rm` layout
+/// for their register/memory-with-register form, differing only in mnemonic.
+fn decode_modrm_to_from_reg(
+    cursor: &mut Cursor<Vec<u8>>,
+    opcode: u8,
+    mnemonic: Mnemonic,
+) -> Result<Instruction> {
+    let w_field = opcode & 1; // how wide is the data, 8 or 16 bits
+    let d_field = opcode >> 1 & 1; // if 1, destination is a register
+
+    let next_byte = cursor.read_u8()?;
+    let rm = next_byte & 0b111;
+    let mode = ModField::try_from(next_byte >> 6)?;
+    let reg = Reg::try_from(w_field << 3 | (next_byte >> 3 & 0b111))?;
+
+    let other = match mode {
+        ModField::RegisterNoDisplacement => {
+            let other_reg = Reg::try_from(w_field << 3 | rm)?;
+            Operand::Register(other_reg)
+        }
+        _ => {
+            let disp: i16 = match mode {
+                ModField::MemoryDisplacement8bit => cursor.read_i8()? as i16,
+                ModField::MemoryDisplacement16bit => cursor.read_i16::<LittleEndian>()?,
+                ModField::MemoryNoDisplacement if rm == 0b110 => {
+                    cursor.read_i16::<LittleEndian>()?
+                }
+                _ => 0,
+            };
+
+            decode_memory_operand(mode, rm, disp)
+        }
+    };
+
+    let (dest, src) = if d_field == 1 {
+        (Operand::Register(reg), other)
+    } else {
+        (other, Operand::Register(reg))
+    };
+
+    Ok(Instruction {
+        mnemonic,
+        dest,
+        src,
+        width: width_of(w_field),
+    })
+}
+
+/// The `100000sw` immediate group: ADD/SUB/CMP (and friends) against a register or
+/// memory destination, with the operation selected by the ModRM `reg` field.
+fn decode_imm_group(cursor: &mut Cursor<Vec<u8>>, opcode: u8, offset: u64) -> Result<Instruction> {
+    let w_field = opcode & 1;
+    let s_field = opcode >> 1 & 1;
+
+    let next_byte = cursor.read_u8()?;
+    let rm = next_byte & 0b111;
+    let op_field = next_byte >> 3 & 0b111;
+    let mode = ModField::try_from(next_byte >> 6)?;
+
+    let mnemonic = match op_field {
+        0b000 => Mnemonic::Add,
+        0b101 => Mnemonic::Sub,
+        0b111 => Mnemonic::Cmp,
+        _ => bail!(
+            "unknown opcode {:#04x} at offset {:#06x} (ModRM reg field {:#05b} selects an \
+             undecoded ADC/SBB/AND/OR/XOR op in the 100000sw group)",
+            opcode,
+            offset,
+            op_field
+        ),
+    };
+
+    let dest = match mode {
+        ModField::RegisterNoDisplacement => {
+            let reg = Reg::try_from(w_field << 3 | rm)?;
+            Operand::Register(reg)
+        }
+        _ => {
+            let disp: i16 = match mode {
+                ModField::MemoryDisplacement8bit => cursor.read_i8()? as i16,
+                ModField::MemoryDisplacement16bit => cursor.read_i16::<LittleEndian>()?,
+                ModField::MemoryNoDisplacement if rm == 0b110 => {
+                    cursor.read_i16::<LittleEndian>()?
+                }
+                _ => 0,
+            };
+
+            decode_memory_operand(mode, rm, disp)
+        }
+    };
+
+    let immediate: i16 = if w_field == 0 || s_field == 1 {
+        // A single byte: either the natural 8-bit width, or sign-extended into 16 bits.
+        cursor.read_i8()? as i16
+    } else {
+        cursor.read_i16::<LittleEndian>()?
+    };
+
+    Ok(Instruction {
+        mnemonic,
+        dest,
+        src: Operand::Immediate(immediate),
+        width: width_of(w_field),
+    })
+}
+
+/// The immediate-to-accumulator short forms shared by ADD/SUB/CMP (`0000010w`,
+/// `0010110w`, `0011110w`).
+fn decode_imm_to_accumulator(
+    cursor: &mut Cursor<Vec<u8>>,
+    opcode: u8,
+    mnemonic: Mnemonic,
+) -> Result<Instruction> {
+    let w_field = opcode & 1;
+    let accumulator = if w_field == 0 { Reg::al } else { Reg::ax };
+
+    let value: i16 = if w_field == 0 {
+        cursor.read_i8()? as i16
+    } else {
+        cursor.read_i16::<LittleEndian>()?
+    };
+
+    Ok(Instruction {
+        mnemonic,
+        dest: Operand::Register(accumulator),
+        src: Operand::Immediate(value),
+        width: width_of(w_field),
+    })
+}
+
+/// 8-bit-relative conditional jumps and the LOOP family: a single signed displacement byte.
+fn decode_jump(cursor: &mut Cursor<Vec<u8>>, mnemonic: Mnemonic) -> Result<Instruction> {
+    let disp = cursor.read_i8()?;
+
+    Ok(Instruction {
+        mnemonic,
+        dest: Operand::Relative(disp),
+        src: Operand::Immediate(0), // unused by single-operand mnemonics
+        width: Width::Word,
+    })
+}
+
+fn decode_imm_to_reg_mem(cursor: &mut Cursor<Vec<u8>>, opcode: u8) -> Result<Instruction> {
+    let w_field = opcode & 0b1;
+    let next_byte = cursor.read_u8()?;
+    let rm = next_byte & 0b111;
+    let mode = ModField::try_from(next_byte >> 6)?;
+
+    let dest = match mode {
+        ModField::RegisterNoDisplacement => {
+            let reg = Reg::try_from(w_field << 3 | rm)?;
+            Operand::Register(reg)
+        }
+        _ => {
+            let disp: i16 = match mode {
+                ModField::MemoryDisplacement8bit => cursor.read_i8()? as i16,
+                ModField::MemoryDisplacement16bit => cursor.read_i16::<LittleEndian>()?,
+                ModField::MemoryNoDisplacement if rm == 0b110 => {
+                    cursor.read_i16::<LittleEndian>()?
+                }
+                _ => 0,
+            };
+
+            decode_memory_operand(mode, rm, disp)
+        }
+    };
+
+    let immediate: i16 = match w_field {
+        0 => cursor.read_i8()? as i16,
+        1 => cursor.read_i16::<LittleEndian>()?,
+        _ => unreachable!(),
+    };
+
+    Ok(Instruction {
+        mnemonic: Mnemonic::Mov,
+        dest,
+        src: Operand::Immediate(immediate),
+        width: width_of(w_field),
+    })
+}
+
+/// The accumulator short forms (`1010000w` memory-to-accumulator, `1010001w`
+/// accumulator-to-memory): a bare 16-bit direct address, no ModRM byte.
+fn decode_mem_accumulator(cursor: &mut Cursor<Vec<u8>>, opcode: u8) -> Result<Instruction> {
+    let w_field = opcode & 1;
+    let to_accumulator = opcode & 0b10 == 0;
+    let accumulator = if w_field == 0 { Reg::al } else { Reg::ax };
+
+    let address = cursor.read_i16::<LittleEndian>()?;
+    let memory = Operand::Memory { base: None, index: None, disp: address };
+
+    let (dest, src) = if to_accumulator {
+        (Operand::Register(accumulator), memory)
+    } else {
+        (memory, Operand::Register(accumulator))
+    };
+
+    Ok(Instruction {
+        mnemonic: Mnemonic::Mov,
+        dest,
+        src,
+        width: width_of(w_field),
+    })
+}
+
+fn decode_imm_to_reg(cursor: &mut Cursor<Vec<u8>>, opcode: u8) -> Result<Instruction> {
+    let w_field = opcode >> 3 & 0b1;
+    let reg = Reg::try_from(w_field << 3 | (opcode & 0b111))?;
+
+    let value: i16 = if w_field == 0 {
+        cursor.read_i8()? as i16
+    } else {
+        cursor.read_i16::<LittleEndian>()?
+    };
+
+    Ok(Instruction {
+        mnemonic: Mnemonic::Mov,
+        dest: Operand::Register(reg),
+        src: Operand::Immediate(value),
+        width: width_of(w_field),
+    })
+}
+
+/// Decode the next instruction at the cursor's current position. Returns `Ok(None)` at
+/// end of input.
+pub fn decode(cursor: &mut Cursor<Vec<u8>>) -> Result<Option<Instruction>> {
+    let offset = cursor.stream_position()?;
+
+    let opcode = match cursor.read_u8() {
+        Ok(byte) => byte,
+        Err(_) => return Ok(None),
+    };
+
+    let instruction = if (opcode >> 2) == 0b100010 {
+        // MOV - Register/memory to/from register
+        decode_modrm_to_from_reg(cursor, opcode, Mnemonic::Mov)?
+    } else if opcode >> 1 == 0b1100011 {
+        // MOV - Immediate to register/memory
+        decode_imm_to_reg_mem(cursor, opcode)?
+    } else if opcode >> 4 == 0b1011 {
+        // MOV - Immediate to register
+        decode_imm_to_reg(cursor, opcode)?
+    } else if opcode >> 1 == 0b1010000 {
+        // MOV - Memory to accumulator
+        decode_mem_accumulator(cursor, opcode)?
+    } else if opcode >> 1 == 0b1010001 {
+        // MOV - Accumulator to memory
+        decode_mem_accumulator(cursor, opcode)?
+    } else if opcode == 0b10001110 {
+        bail!(
+            "unknown opcode {:#04x} at offset {:#06x} (register/memory to segment register isn't decoded)",
+            opcode,
+            offset
+        );
+    } else if opcode == 0b10001100 {
+        bail!(
+            "unknown opcode {:#04x} at offset {:#06x} (segment register to register/memory isn't decoded)",
+            opcode,
+            offset
+        );
+    } else if (opcode >> 2) == 0b000000 {
+        // ADD - Register/memory with register
+        decode_modrm_to_from_reg(cursor, opcode, Mnemonic::Add)?
+    } else if (opcode >> 2) == 0b001010 {
+        // SUB - Register/memory with register
+        decode_modrm_to_from_reg(cursor, opcode, Mnemonic::Sub)?
+    } else if (opcode >> 2) == 0b001110 {
+        // CMP - Register/memory with register
+        decode_modrm_to_from_reg(cursor, opcode, Mnemonic::Cmp)?
+    } else if (opcode >> 2) == 0b100000 {
+        // ADD/SUB/CMP (and friends) - Immediate with register/memory
+        decode_imm_group(cursor, opcode, offset)?
+    } else if (opcode >> 1) == 0b0000010 {
+        decode_imm_to_accumulator(cursor, opcode, Mnemonic::Add)?
+    } else if (opcode >> 1) == 0b0010110 {
+        decode_imm_to_accumulator(cursor, opcode, Mnemonic::Sub)?
+    } else if (opcode >> 1) == 0b0011110 {
+        decode_imm_to_accumulator(cursor, opcode, Mnemonic::Cmp)?
+    } else if opcode == 0x74 {
+        decode_jump(cursor, Mnemonic::Je)?
+    } else if opcode == 0x75 {
+        decode_jump(cursor, Mnemonic::Jne)?
+    } else if opcode == 0x7c {
+        decode_jump(cursor, Mnemonic::Jl)?
+    } else if opcode == 0x7d {
+        decode_jump(cursor, Mnemonic::Jge)?
+    } else if opcode == 0x7e {
+        decode_jump(cursor, Mnemonic::Jle)?
+    } else if opcode == 0x7f {
+        decode_jump(cursor, Mnemonic::Jg)?
+    } else if opcode == 0x72 {
+        decode_jump(cursor, Mnemonic::Jb)?
+    } else if opcode == 0x73 {
+        decode_jump(cursor, Mnemonic::Jae)?
+    } else if opcode == 0x76 {
+        decode_jump(cursor, Mnemonic::Jbe)?
+    } else if opcode == 0x77 {
+        decode_jump(cursor, Mnemonic::Ja)?
+    } else if opcode == 0x7a {
+        decode_jump(cursor, Mnemonic::Jp)?
+    } else if opcode == 0x7b {
+        decode_jump(cursor, Mnemonic::Jnp)?
+    } else if opcode == 0x70 {
+        decode_jump(cursor, Mnemonic::Jo)?
+    } else if opcode == 0x71 {
+        decode_jump(cursor, Mnemonic::Jno)?
+    } else if opcode == 0x78 {
+        decode_jump(cursor, Mnemonic::Js)?
+    } else if opcode == 0x79 {
+        decode_jump(cursor, Mnemonic::Jns)?
+    } else if opcode == 0xe2 {
+        decode_jump(cursor, Mnemonic::Loop)?
+    } else if opcode == 0xe1 {
+        decode_jump(cursor, Mnemonic::Loopz)?
+    } else if opcode == 0xe0 {
+        decode_jump(cursor, Mnemonic::Loopnz)?
+    } else if opcode == 0xe3 {
+        decode_jump(cursor, Mnemonic::Jcxz)?
+    } else {
+        bail!("unknown opcode {:#04x} at offset {:#06x}", opcode, offset);
+    };
+
+    Ok(Some(instruction))
+}
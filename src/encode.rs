@@ -0,0 +1,384 @@
+use crate::instruction::{Instruction, Mnemonic, Width};
+use crate::operand::Operand;
+use crate::reg::Reg;
+
+fn width_bit(width: Width) -> u8 {
+    match width {
+        Width::Byte => 0,
+        Width::Word => 1,
+    }
+}
+
+/// Encode a memory operand's `mod`/`rm` bits and trailing displacement bytes, per table
+/// 4-10 (the inverse of `decode::decode_memory_operand`).
+///
+/// `[bp]` with no displacement can't be told apart from the direct-address form (both
+/// are `mod=00, rm=110`), so it's encoded as `mod=01, disp8=0` instead - the same
+/// workaround every 8086 assembler uses.
+fn encode_memory_operand(base: Option<Reg>, index: Option<Reg>, disp: i16) -> (u8, u8, Vec<u8>) {
+    let rm = match (base, index) {
+        (None, None) => 0b110,
+        (Some(Reg::bp), None) => 0b110,
+        (Some(Reg::bx), Some(Reg::si)) => 0b000,
+        (Some(Reg::bx), Some(Reg::di)) => 0b001,
+        (Some(Reg::bp), Some(Reg::si)) => 0b010,
+        (Some(Reg::bp), Some(Reg::di)) => 0b011,
+        (None, Some(Reg::si)) => 0b100,
+        (None, Some(Reg::di)) => 0b101,
+        (Some(Reg::bx), None) => 0b111,
+        _ => unreachable!("not an addressing form table 4-10 produces"),
+    };
+
+    if base.is_none() && index.is_none() {
+        return (0b00, rm, disp.to_le_bytes().to_vec());
+    }
+
+    if disp == 0 && !(base == Some(Reg::bp) && index.is_none()) {
+        (0b00, rm, vec![])
+    } else if let Ok(disp8) = i8::try_from(disp) {
+        (0b01, rm, vec![disp8 as u8])
+    } else {
+        (0b10, rm, disp.to_le_bytes().to_vec())
+    }
+}
+
+/// Encode any operand as a ModRM `mod`/`rm` pair, for use as the "other" (non-`reg`-field)
+/// side of a ModRM instruction.
+fn encode_modrm_operand(operand: &Operand) -> (u8, u8, Vec<u8>) {
+    match operand {
+        Operand::Register(reg) => (0b11, *reg as u8 & 0b111, vec![]),
+        Operand::Memory { base, index, disp } => encode_memory_operand(*base, *index, *disp),
+        _ => unreachable!("not a ModRM-encodable operand"),
+    }
+}
+
+/// The `mod`/`reg`/`rm` register-or-memory forms shared by MOV/ADD/SUB/CMP: whichever
+/// side is a register supplies the `reg` field, and the `d` bit records which side that
+/// was. When both sides are registers, `d=1` (dest is `reg`) is the canonical choice.
+fn encode_modrm_to_from_reg(opcode_prefix: u8, dest: &Operand, src: &Operand, width: Width) -> Vec<u8> {
+    let w = width_bit(width);
+
+    let (d, reg, other) = match (dest, src) {
+        (Operand::Register(reg), other) => (1, reg, other),
+        (other, Operand::Register(reg)) => (0, reg, other),
+        _ => unreachable!("mod/reg/rm forms need a register on at least one side"),
+    };
+
+    let (mode, rm, disp) = encode_modrm_operand(other);
+
+    let mut bytes = vec![opcode_prefix << 2 | d << 1 | w, mode << 6 | (*reg as u8 & 0b111) << 3 | rm];
+    bytes.extend(disp);
+    bytes
+}
+
+/// Encode an immediate, using its natural width or (for word-width ops where the value
+/// fits) a sign-extended single byte - whichever the `s`/`w` bits allow. Returns the
+/// chosen `s` bit alongside the encoded bytes.
+fn encode_immediate(value: i16, width: Width) -> (u8, Vec<u8>) {
+    match width {
+        Width::Byte => (0, vec![value as u8]),
+        Width::Word => match i8::try_from(value) {
+            Ok(short) => (1, vec![short as u8]),
+            Err(_) => (0, value.to_le_bytes().to_vec()),
+        },
+    }
+}
+
+/// The `100000sw` immediate group: ADD/SUB/CMP against a register or memory destination.
+fn encode_imm_group(op_field: u8, dest: &Operand, value: i16, width: Width) -> Vec<u8> {
+    let w = width_bit(width);
+    let (s, immediate) = encode_immediate(value, width);
+
+    let (mode, rm, disp) = encode_modrm_operand(dest);
+
+    let mut bytes = vec![0b100000 << 2 | s << 1 | w, mode << 6 | op_field << 3 | rm];
+    bytes.extend(disp);
+    bytes.extend(immediate);
+    bytes
+}
+
+/// The immediate-to-accumulator short forms for ADD/SUB/CMP: no ModRM byte at all.
+fn encode_imm_to_accumulator(opcode_prefix: u8, value: i16, width: Width) -> Vec<u8> {
+    let w = width_bit(width);
+    let immediate: Vec<u8> = match width {
+        Width::Byte => vec![value as u8],
+        Width::Word => value.to_le_bytes().to_vec(),
+    };
+
+    let mut bytes = vec![opcode_prefix << 1 | w];
+    bytes.extend(immediate);
+    bytes
+}
+
+/// The immediate-to-register short form (`1011wreg`): always shorter than the
+/// immediate-to-register/memory ModRM form, so it's preferred whenever the destination
+/// is a register.
+fn encode_imm_to_reg(reg: Reg, value: i16, width: Width) -> Vec<u8> {
+    let w = width_bit(width);
+    let immediate: Vec<u8> = match width {
+        Width::Byte => vec![value as u8],
+        Width::Word => value.to_le_bytes().to_vec(),
+    };
+
+    let mut bytes = vec![0b1011 << 4 | w << 3 | (reg as u8 & 0b111)];
+    bytes.extend(immediate);
+    bytes
+}
+
+/// The immediate-to-register/memory form (`1100011w`), for immediate MOVs with a memory
+/// destination (register destinations use the shorter `encode_imm_to_reg` form instead).
+fn encode_imm_to_reg_mem(dest: &Operand, value: i16, width: Width) -> Vec<u8> {
+    let w = width_bit(width);
+    let (mode, rm, disp) = encode_modrm_operand(dest);
+
+    let immediate: Vec<u8> = match width {
+        Width::Byte => vec![value as u8],
+        Width::Word => value.to_le_bytes().to_vec(),
+    };
+
+    let mut bytes = vec![0b1100011 << 1 | w, mode << 6 | rm];
+    bytes.extend(disp);
+    bytes.extend(immediate);
+    bytes
+}
+
+fn jump_opcode(mnemonic: Mnemonic) -> u8 {
+    match mnemonic {
+        Mnemonic::Jo => 0x70,
+        Mnemonic::Jno => 0x71,
+        Mnemonic::Jb => 0x72,
+        Mnemonic::Jae => 0x73,
+        Mnemonic::Je => 0x74,
+        Mnemonic::Jne => 0x75,
+        Mnemonic::Jbe => 0x76,
+        Mnemonic::Ja => 0x77,
+        Mnemonic::Js => 0x78,
+        Mnemonic::Jns => 0x79,
+        Mnemonic::Jp => 0x7a,
+        Mnemonic::Jnp => 0x7b,
+        Mnemonic::Jl => 0x7c,
+        Mnemonic::Jge => 0x7d,
+        Mnemonic::Jle => 0x7e,
+        Mnemonic::Jg => 0x7f,
+        Mnemonic::Loopnz => 0xe0,
+        Mnemonic::Loopz => 0xe1,
+        Mnemonic::Loop => 0xe2,
+        Mnemonic::Jcxz => 0xe3,
+        _ => unreachable!("not a single-operand branch mnemonic"),
+    }
+}
+
+/// Re-encode a decoded instruction back into 8086 machine code. The inverse of
+/// `decode::decode`, for every form the decoder supports: given `encode(decode(bytes)?)`,
+/// the result is a shortest/canonical encoding of the same instruction, which need not be
+/// byte-identical to `bytes` but decodes back to an equal `Instruction`.
+pub fn encode(instruction: &Instruction) -> Vec<u8> {
+    if instruction.mnemonic.is_single_operand() {
+        let Operand::Relative(disp) = instruction.dest else {
+            unreachable!("single-operand branch without a relative target")
+        };
+        return vec![jump_opcode(instruction.mnemonic), disp as u8];
+    }
+
+    let width = instruction.width;
+
+    match instruction.mnemonic {
+        Mnemonic::Mov => match (instruction.dest, instruction.src) {
+            (Operand::Register(reg), Operand::Immediate(value)) => {
+                encode_imm_to_reg(reg, value, width)
+            }
+            (dest @ Operand::Memory { .. }, Operand::Immediate(value)) => {
+                encode_imm_to_reg_mem(&dest, value, width)
+            }
+            (Operand::Register(Reg::al | Reg::ax), Operand::Memory { base: None, index: None, disp }) => {
+                // Memory-to-accumulator short form (`1010000w`): no ModRM byte.
+                let mut bytes = vec![0b1010000 << 1 | width_bit(width)];
+                bytes.extend(disp.to_le_bytes());
+                bytes
+            }
+            (Operand::Memory { base: None, index: None, disp }, Operand::Register(Reg::al | Reg::ax)) => {
+                // Accumulator-to-memory short form (`1010001w`).
+                let mut bytes = vec![0b1010001 << 1 | width_bit(width)];
+                bytes.extend(disp.to_le_bytes());
+                bytes
+            }
+            (dest, src) => encode_modrm_to_from_reg(0b100010, &dest, &src, width),
+        },
+        Mnemonic::Add | Mnemonic::Sub | Mnemonic::Cmp => {
+            let accumulator = match width {
+                Width::Byte => Operand::Register(Reg::al),
+                Width::Word => Operand::Register(Reg::ax),
+            };
+            let accumulator_prefix = match instruction.mnemonic {
+                Mnemonic::Add => 0b0000010,
+                Mnemonic::Sub => 0b0010110,
+                Mnemonic::Cmp => 0b0011110,
+                _ => unreachable!(),
+            };
+            let op_field = match instruction.mnemonic {
+                Mnemonic::Add => 0b000,
+                Mnemonic::Sub => 0b101,
+                Mnemonic::Cmp => 0b111,
+                _ => unreachable!(),
+            };
+            let modrm_prefix = match instruction.mnemonic {
+                Mnemonic::Add => 0b000000,
+                Mnemonic::Sub => 0b001010,
+                Mnemonic::Cmp => 0b001110,
+                _ => unreachable!(),
+            };
+
+            match (instruction.dest, instruction.src) {
+                (dest, Operand::Immediate(value)) if dest == accumulator => {
+                    encode_imm_to_accumulator(accumulator_prefix, value, width)
+                }
+                (dest, Operand::Immediate(value)) => encode_imm_group(op_field, &dest, value, width),
+                (dest, src) => encode_modrm_to_from_reg(modrm_prefix, &dest, &src, width),
+            }
+        }
+        _ => unreachable!("not an encodable instruction form"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::decode;
+    use std::io::Cursor;
+
+    /// Encode `instruction`, decode the result, and assert it round-trips to an equal
+    /// `Instruction` with no leftover or missing bytes.
+    fn assert_round_trips(instruction: Instruction) {
+        let bytes = encode(&instruction);
+        let mut cursor = Cursor::new(bytes.clone());
+
+        let decoded = decode(&mut cursor)
+            .expect("encoded bytes should always redecode")
+            .expect("encoded bytes should never be empty");
+
+        assert_eq!(decoded, instruction, "encoded as {:x?}", bytes);
+        assert_eq!(cursor.position() as usize, bytes.len(), "encoded as {:x?}", bytes);
+    }
+
+    #[test]
+    fn round_trips_mov_register_to_register() {
+        assert_round_trips(Instruction {
+            mnemonic: Mnemonic::Mov,
+            dest: Operand::Register(Reg::bx),
+            src: Operand::Register(Reg::ax),
+            width: Width::Word,
+        });
+    }
+
+    #[test]
+    fn round_trips_mov_immediate_to_register() {
+        assert_round_trips(Instruction {
+            mnemonic: Mnemonic::Mov,
+            dest: Operand::Register(Reg::cl),
+            src: Operand::Immediate(-56),
+            width: Width::Byte,
+        });
+    }
+
+    #[test]
+    fn round_trips_mov_immediate_to_memory() {
+        assert_round_trips(Instruction {
+            mnemonic: Mnemonic::Mov,
+            dest: Operand::Memory { base: Some(Reg::bx), index: Some(Reg::si), disp: 0 },
+            src: Operand::Immediate(1000),
+            width: Width::Word,
+        });
+    }
+
+    #[test]
+    fn round_trips_mov_register_to_direct_address() {
+        assert_round_trips(Instruction {
+            mnemonic: Mnemonic::Mov,
+            dest: Operand::Memory { base: None, index: None, disp: 0x1234 },
+            src: Operand::Register(Reg::ax),
+            width: Width::Word,
+        });
+    }
+
+    #[test]
+    fn round_trips_mov_register_to_bp_with_no_displacement() {
+        // [bp] with disp=0 can't use mod=00 (that's the direct-address form), so it has
+        // to come back out the other side as an explicit zero 8-bit displacement.
+        assert_round_trips(Instruction {
+            mnemonic: Mnemonic::Mov,
+            dest: Operand::Memory { base: Some(Reg::bp), index: None, disp: 0 },
+            src: Operand::Register(Reg::dl),
+            width: Width::Byte,
+        });
+    }
+
+    #[test]
+    fn round_trips_mov_memory_with_wide_displacement() {
+        assert_round_trips(Instruction {
+            mnemonic: Mnemonic::Mov,
+            dest: Operand::Register(Reg::di),
+            src: Operand::Memory { base: Some(Reg::bx), index: None, disp: 4000 },
+            width: Width::Word,
+        });
+    }
+
+    #[test]
+    fn round_trips_add_register_to_register() {
+        assert_round_trips(Instruction {
+            mnemonic: Mnemonic::Add,
+            dest: Operand::Register(Reg::bx),
+            src: Operand::Register(Reg::ax),
+            width: Width::Word,
+        });
+    }
+
+    #[test]
+    fn round_trips_add_immediate_to_accumulator() {
+        assert_round_trips(Instruction {
+            mnemonic: Mnemonic::Add,
+            dest: Operand::Register(Reg::ax),
+            src: Operand::Immediate(300),
+            width: Width::Word,
+        });
+    }
+
+    #[test]
+    fn round_trips_sub_immediate_to_memory() {
+        assert_round_trips(Instruction {
+            mnemonic: Mnemonic::Sub,
+            dest: Operand::Memory { base: Some(Reg::bp), index: Some(Reg::di), disp: 10 },
+            src: Operand::Immediate(5),
+            width: Width::Byte,
+        });
+    }
+
+    #[test]
+    fn round_trips_cmp_immediate_to_register() {
+        assert_round_trips(Instruction {
+            mnemonic: Mnemonic::Cmp,
+            dest: Operand::Register(Reg::bx),
+            src: Operand::Immediate(-1),
+            width: Width::Word,
+        });
+    }
+
+    #[test]
+    fn round_trips_conditional_jump() {
+        assert_round_trips(Instruction {
+            mnemonic: Mnemonic::Jne,
+            dest: Operand::Relative(-20),
+            src: Operand::Immediate(0),
+            width: Width::Word,
+        });
+    }
+
+    #[test]
+    fn round_trips_loop() {
+        assert_round_trips(Instruction {
+            mnemonic: Mnemonic::Loop,
+            dest: Operand::Relative(10),
+            src: Operand::Immediate(0),
+            width: Width::Word,
+        });
+    }
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+
+use crate::instruction::Width;
+
+/// The subset of the 8086 FLAGS register this simulator tracks, updated after each
+/// arithmetic/compare instruction.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Flags {
+    pub carry: bool,
+    pub parity: bool,
+    pub auxiliary_carry: bool,
+    pub zero: bool,
+    pub sign: bool,
+    pub overflow: bool,
+}
+
+impl Flags {
+    /// Compute `dest - src` (if `is_sub`) or `dest + src`, update all flags from the
+    /// result, and return it so the caller can decide whether to write it back (ADD/SUB
+    /// do; CMP discards it).
+    pub fn apply_arithmetic(&mut self, dest: u16, src: u16, width: Width, is_sub: bool) -> u16 {
+        let mask: u32 = match width {
+            Width::Byte => 0x00ff,
+            Width::Word => 0xffff,
+        };
+        let sign_bit: u32 = match width {
+            Width::Byte => 0x0080,
+            Width::Word => 0x8000,
+        };
+
+        let dest = dest as u32 & mask;
+        let src = src as u32 & mask;
+
+        let (result, carry) = if is_sub {
+            (dest.wrapping_sub(src) & mask, dest < src)
+        } else {
+            let sum = dest + src;
+            (sum & mask, sum > mask)
+        };
+
+        self.carry = carry;
+        self.zero = result == 0;
+        self.sign = result & sign_bit != 0;
+        self.parity = (result as u8).count_ones().is_multiple_of(2);
+        self.auxiliary_carry = if is_sub {
+            (dest & 0x000f) < (src & 0x000f)
+        } else {
+            (dest & 0x000f) + (src & 0x000f) > 0x000f
+        };
+        self.overflow = if is_sub {
+            (dest ^ src) & sign_bit != 0 && (dest ^ result) & sign_bit != 0
+        } else {
+            !(dest ^ src) & (dest ^ result) & sign_bit != 0
+        };
+
+        result as u16
+    }
+
+    /// The flags that differ between `self` and `previous`, in FLAGS-register bit order.
+    pub fn changes_from(self, previous: Flags) -> Vec<FlagChange> {
+        let mut changes = Vec::new();
+        let mut note = |name, from, to| {
+            if from != to {
+                changes.push(FlagChange { name, from, to });
+            }
+        };
+
+        note("CF", previous.carry, self.carry);
+        note("PF", previous.parity, self.parity);
+        note("AF", previous.auxiliary_carry, self.auxiliary_carry);
+        note("ZF", previous.zero, self.zero);
+        note("SF", previous.sign, self.sign);
+        note("OF", previous.overflow, self.overflow);
+
+        changes
+    }
+}
+
+impl fmt::Display for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.carry {
+            write!(f, "C")?;
+        }
+        if self.parity {
+            write!(f, "P")?;
+        }
+        if self.auxiliary_carry {
+            write!(f, "A")?;
+        }
+        if self.zero {
+            write!(f, "Z")?;
+        }
+        if self.sign {
+            write!(f, "S")?;
+        }
+        if self.overflow {
+            write!(f, "O")?;
+        }
+        Ok(())
+    }
+}
+
+/// A single flag's before/after state, e.g. `ZF:0->1`.
+pub struct FlagChange {
+    name: &'static str,
+    from: bool,
+    to: bool,
+}
+
+impl fmt::Display for FlagChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}->{}", self.name, self.from as u8, self.to as u8)
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+
+use crate::operand::Operand;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Width {
+    Byte,
+    Word,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mnemonic {
+    Mov,
+    Add,
+    Sub,
+    Cmp,
+    Je,
+    Jne,
+    Jl,
+    Jge,
+    Jle,
+    Jg,
+    Jb,
+    Jae,
+    Jbe,
+    Ja,
+    Jp,
+    Jnp,
+    Jo,
+    Jno,
+    Js,
+    Jns,
+    Loop,
+    Loopz,
+    Loopnz,
+    Jcxz,
+}
+
+impl Mnemonic {
+    /// Conditional jumps and loop instructions take a single relative target, not a dest/src pair.
+    pub fn is_single_operand(&self) -> bool {
+        matches!(
+            self,
+            Mnemonic::Je
+                | Mnemonic::Jne
+                | Mnemonic::Jl
+                | Mnemonic::Jge
+                | Mnemonic::Jle
+                | Mnemonic::Jg
+                | Mnemonic::Jb
+                | Mnemonic::Jae
+                | Mnemonic::Jbe
+                | Mnemonic::Ja
+                | Mnemonic::Jp
+                | Mnemonic::Jnp
+                | Mnemonic::Jo
+                | Mnemonic::Jno
+                | Mnemonic::Js
+                | Mnemonic::Jns
+                | Mnemonic::Loop
+                | Mnemonic::Loopz
+                | Mnemonic::Loopnz
+                | Mnemonic::Jcxz
+        )
+    }
+}
+
+impl fmt::Display for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mnemonic::Mov => write!(f, "mov"),
+            Mnemonic::Add => write!(f, "add"),
+            Mnemonic::Sub => write!(f, "sub"),
+            Mnemonic::Cmp => write!(f, "cmp"),
+            Mnemonic::Je => write!(f, "je"),
+            Mnemonic::Jne => write!(f, "jne"),
+            Mnemonic::Jl => write!(f, "jl"),
+            Mnemonic::Jge => write!(f, "jge"),
+            Mnemonic::Jle => write!(f, "jle"),
+            Mnemonic::Jg => write!(f, "jg"),
+            Mnemonic::Jb => write!(f, "jb"),
+            Mnemonic::Jae => write!(f, "jae"),
+            Mnemonic::Jbe => write!(f, "jbe"),
+            Mnemonic::Ja => write!(f, "ja"),
+            Mnemonic::Jp => write!(f, "jp"),
+            Mnemonic::Jnp => write!(f, "jnp"),
+            Mnemonic::Jo => write!(f, "jo"),
+            Mnemonic::Jno => write!(f, "jno"),
+            Mnemonic::Js => write!(f, "js"),
+            Mnemonic::Jns => write!(f, "jns"),
+            Mnemonic::Loop => write!(f, "loop"),
+            Mnemonic::Loopz => write!(f, "loopz"),
+            Mnemonic::Loopnz => write!(f, "loopnz"),
+            Mnemonic::Jcxz => write!(f, "jcxz"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub mnemonic: Mnemonic,
+    pub dest: Operand,
+    pub src: Operand,
+    pub width: Width,
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.mnemonic.is_single_operand() {
+            return write!(f, "{} {}", self.mnemonic, self.dest);
+        }
+
+        write!(f, "{} {}, ", self.mnemonic, self.dest)?;
+
+        // A memory destination with an immediate source is otherwise ambiguous
+        // about operand width, so it needs an explicit `byte`/`word` marker.
+        match (self.dest, self.src) {
+            (Operand::Memory { .. }, Operand::Immediate(value)) => match self.width {
+                Width::Byte => write!(f, "byte {}", value),
+                Width::Word => write!(f, "word {}", value),
+            },
+            _ => write!(f, "{}", self.src),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::fmt;
+
+use crate::reg::Reg;
+
+/// A signed displacement, formatted as `" + N"` / `" - N"`, or nothing when zero.
+struct DispSuffix(i16);
+
+impl fmt::Display for DispSuffix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            0 => Ok(()),
+            value if value > 0 => write!(f, " + {}", value),
+            value => write!(f, " - {}", value.abs()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Register(Reg),
+    Memory {
+        base: Option<Reg>,
+        index: Option<Reg>,
+        disp: i16,
+    },
+    Immediate(i16),
+    /// An 8-bit relative jump displacement, shown NASM-style relative to `$`
+    /// (the address of the jump instruction itself).
+    Relative(i8),
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operand::Register(reg) => write!(f, "{}", reg),
+            Operand::Immediate(value) => write!(f, "{}", value),
+            Operand::Memory { base, index, disp } => match (base, index) {
+                (Some(base), Some(index)) => write!(f, "[{} + {}{}]", base, index, DispSuffix(*disp)),
+                (Some(base), None) => write!(f, "[{}{}]", base, DispSuffix(*disp)),
+                (None, Some(index)) => write!(f, "[{}{}]", index, DispSuffix(*disp)),
+                (None, None) => write!(f, "[{}]", disp),
+            },
+            Operand::Relative(disp) => {
+                // The displacement is relative to the end of this (2-byte) instruction.
+                let offset = *disp as i16 + 2;
+                if offset >= 0 {
+                    write!(f, "$+{}", offset)
+                } else {
+                    write!(f, "${}", offset)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::fmt;
+
+use anyhow::{bail, Result};
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types, dead_code)]
+pub enum Reg {
+    al = 0b0000,
+    cl = 0b0001,
+    dl = 0b0010,
+    bl = 0b0011,
+    ah = 0b0100,
+    ch = 0b0101,
+    dh = 0b0110,
+    bh = 0b0111,
+    ax = 0b1000,
+    cx = 0b1001,
+    dx = 0b1010,
+    bx = 0b1011,
+    sp = 0b1100,
+    bp = 0b1101,
+    si = 0b1110,
+    di = 0b1111,
+}
+
+impl TryFrom<u8> for Reg {
+    type Error = anyhow::Error;
+
+    fn try_from(code: u8) -> Result<Self> {
+        Ok(match code {
+            0b0000 => Reg::al,
+            0b0001 => Reg::cl,
+            0b0010 => Reg::dl,
+            0b0011 => Reg::bl,
+            0b0100 => Reg::ah,
+            0b0101 => Reg::ch,
+            0b0110 => Reg::dh,
+            0b0111 => Reg::bh,
+            0b1000 => Reg::ax,
+            0b1001 => Reg::cx,
+            0b1010 => Reg::dx,
+            0b1011 => Reg::bx,
+            0b1100 => Reg::sp,
+            0b1101 => Reg::bp,
+            0b1110 => Reg::si,
+            0b1111 => Reg::di,
+            _ => bail!("register code {:#06b} doesn't fit in the 4 bits a w/reg field provides", code),
+        })
+    }
+}
+
+impl fmt::Display for Reg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}